@@ -0,0 +1,91 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Generic timer counter backend for Linux on `aarch64`.
+//!
+//! Unlike the x86 TSC, the architectural generic timer (`cntvct_el0`) is guaranteed to
+//! be monotonic, to tick at a constant rate, and to be synchronized across cores, so no
+//! per-core deviation calibration is needed. The one thing that *can't* be trusted
+//! blindly is the firmware-programmed counter frequency (`cntfrq_el0`), which is
+//! occasionally misconfigured, so it's validated against a short wall-clock measurement
+//! at calibration time.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant as StdInstant;
+
+/// Wall-clock duration used to sanity-check `cntfrq_el0` at calibration time.
+const VALIDATION_INTERVAL: Duration = Duration::from_millis(10);
+/// If the measured frequency disagrees with `cntfrq_el0` by more than this factor, the
+/// reported frequency is untrusted and the backend falls back.
+const MAX_DEVIATION: f64 = 0.1;
+
+static AVAILABLE: AtomicBool = AtomicBool::new(false);
+static NANOS_PER_CYCLE_BITS: AtomicU64 = AtomicU64::new(0);
+
+#[used]
+#[link_section = ".init_array"]
+static CALIBRATE_ON_LOAD: extern "C" fn() = calibrate_ctor;
+
+extern "C" fn calibrate_ctor() {
+    calibrate();
+}
+
+#[inline]
+pub fn current_cycle() -> u64 {
+    let cycle: u64;
+    unsafe {
+        std::arch::asm!("mrs {0}, cntvct_el0", out(reg) cycle, options(nomem, nostack));
+    }
+    cycle
+}
+
+#[inline]
+pub fn is_tsc_available() -> bool {
+    AVAILABLE.load(Ordering::Relaxed)
+}
+
+#[inline]
+pub fn nanos_per_cycle() -> f64 {
+    f64::from_bits(NANOS_PER_CYCLE_BITS.load(Ordering::Relaxed))
+}
+
+#[inline]
+fn counter_freq_hz() -> u64 {
+    let freq: u64;
+    unsafe {
+        std::arch::asm!("mrs {0}, cntfrq_el0", out(reg) freq, options(nomem, nostack));
+    }
+    freq
+}
+
+fn calibrate() {
+    let freq_hz = counter_freq_hz();
+    if freq_hz == 0 {
+        AVAILABLE.store(false, Ordering::Relaxed);
+        return;
+    }
+    let firmware_nanos_per_cycle = 1e9 / freq_hz as f64;
+
+    let wall_start = StdInstant::now();
+    let cycle_start = current_cycle();
+    std::thread::sleep(VALIDATION_INTERVAL);
+    let cycle_end = current_cycle();
+    let wall_elapsed = wall_start.elapsed();
+
+    let cycles = cycle_end.saturating_sub(cycle_start);
+    if cycles == 0 {
+        AVAILABLE.store(false, Ordering::Relaxed);
+        return;
+    }
+    let measured_nanos_per_cycle = wall_elapsed.as_nanos() as f64 / cycles as f64;
+
+    let deviates = ((measured_nanos_per_cycle - firmware_nanos_per_cycle)
+        / firmware_nanos_per_cycle)
+        .abs()
+        > MAX_DEVIATION;
+
+    NANOS_PER_CYCLE_BITS.store(firmware_nanos_per_cycle.to_bits(), Ordering::Relaxed);
+    AVAILABLE.store(!deviates, Ordering::Relaxed);
+}
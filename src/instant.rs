@@ -0,0 +1,199 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+#[cfg(all(feature = "atomic", target_has_atomic = "64"))]
+use std::sync::atomic::AtomicU64;
+#[cfg(all(feature = "atomic", target_has_atomic = "64"))]
+use std::sync::atomic::Ordering;
+
+use crate::current_cycle;
+use crate::nanos_per_cycle;
+
+/// A measurement of a monotonically increasing clock, similar to [`std::time::Instant`].
+///
+/// Unlike [`std::time::Instant`], a `fastant::Instant` is backed by a raw cycle counter
+/// (TSC on Linux x86/x86_64, the generic timer counter on Linux aarch64, a fallback clock
+/// elsewhere) rather than a `timespec`, which
+/// makes it significantly cheaper to obtain.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct Instant(u64);
+
+impl Instant {
+    /// Returns an instant corresponding to "now".
+    #[inline]
+    pub fn now() -> Instant {
+        Instant(current_cycle())
+    }
+
+    /// Returns an instant close to "now" at a fraction of the cost of [`Instant::now`].
+    ///
+    /// This loads the last value refreshed by a running [`Upkeep`](crate::Upkeep) thread
+    /// instead of reading the clock directly, trading accuracy (bounded by the upkeep
+    /// thread's refresh interval) for an essentially free read. Falls back to
+    /// [`Instant::now`] if no upkeep thread is currently running.
+    #[cfg(feature = "upkeep")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "upkeep")))]
+    #[inline]
+    pub fn recent() -> Instant {
+        if crate::upkeep::is_active() {
+            Instant(crate::upkeep::recent_cycle())
+        } else {
+            Instant::now()
+        }
+    }
+
+    /// Returns the amount of time elapsed since this instant was created.
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        Instant::now().duration_since(*self)
+    }
+
+    /// Returns the amount of time elapsed from another instant to this one.
+    ///
+    /// Returns `Duration::ZERO` if `earlier` is later than `self`, mirroring
+    /// [`std::time::Instant::duration_since`].
+    #[inline]
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        let cycles = self.0.saturating_sub(earlier.0);
+        Duration::from_nanos((cycles as f64 * nanos_per_cycle()) as u64)
+    }
+
+    /// Converts this instant into a unix timestamp in nanoseconds, using `anchor` as the
+    /// reference point between the cycle counter and real-world time.
+    #[inline]
+    pub fn as_unix_nanos(&self, anchor: &Anchor) -> u64 {
+        let offset_cycles = self.0 as i128 - anchor.zero.0 as i128;
+        let offset_nanos = (offset_cycles as f64 * nanos_per_cycle()) as i128;
+        (anchor.unix_time_ns as i128 + offset_nanos).max(0) as u64
+    }
+
+    /// Converts this instant into a [`std::time::SystemTime`], using `anchor` as the
+    /// reference point between the cycle counter and real-world time.
+    ///
+    /// Times before the Unix epoch are clamped to [`UNIX_EPOCH`] rather than wrapping,
+    /// mirroring the clamping already done by [`Instant::as_unix_nanos`].
+    #[cfg(feature = "system-time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "system-time")))]
+    #[inline]
+    pub fn into_system_time(&self, anchor: &Anchor) -> SystemTime {
+        let (secs, subsec_nanos) = split_unix_nanos(self.as_unix_nanos(anchor));
+        UNIX_EPOCH + Duration::new(secs, subsec_nanos)
+    }
+
+    /// Converts this instant into a [`chrono::DateTime<Utc>`](chrono::DateTime), using
+    /// `anchor` as the reference point between the cycle counter and real-world time.
+    #[cfg(feature = "chrono")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "chrono")))]
+    #[inline]
+    pub fn into_chrono_date_time(&self, anchor: &Anchor) -> chrono::DateTime<chrono::Utc> {
+        let (secs, subsec_nanos) = split_unix_nanos(self.as_unix_nanos(anchor));
+        chrono::DateTime::from_timestamp(secs as i64, subsec_nanos)
+            .unwrap_or(chrono::DateTime::UNIX_EPOCH)
+    }
+
+    /// Converts this instant into a [`time::OffsetDateTime`], using `anchor` as the
+    /// reference point between the cycle counter and real-world time.
+    #[cfg(feature = "time")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "time")))]
+    #[inline]
+    pub fn into_offset_date_time(&self, anchor: &Anchor) -> time::OffsetDateTime {
+        let unix_nanos = self.as_unix_nanos(anchor);
+        time::OffsetDateTime::from_unix_timestamp_nanos(unix_nanos as i128)
+            .unwrap_or(time::OffsetDateTime::UNIX_EPOCH)
+    }
+
+    #[inline]
+    pub(crate) fn as_inner(self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    pub(crate) fn from_inner(inner: u64) -> Instant {
+        Instant(inner)
+    }
+}
+
+/// Splits a unix-nanos timestamp into seconds and sub-second nanoseconds, mirroring the
+/// `Timespec { tv_sec, tv_nsec }` decomposition used by std's unix time layer.
+#[cfg(any(feature = "system-time", feature = "chrono"))]
+#[inline]
+fn split_unix_nanos(unix_nanos: u64) -> (u64, u32) {
+    (
+        unix_nanos / 1_000_000_000,
+        (unix_nanos % 1_000_000_000) as u32,
+    )
+}
+
+/// An anchor between the cycle counter used by [`Instant`] and real-world (unix) time.
+///
+/// Because [`Instant`] only stores a raw cycle count, converting it to a calendar time
+/// requires a reference point relating a particular cycle count to a particular unix
+/// timestamp. `Anchor::new()` captures that reference point at the moment it's called;
+/// reuse the same `Anchor` for every conversion so they stay consistent with each other.
+#[derive(Clone, Copy, Debug)]
+pub struct Anchor {
+    unix_time_ns: u64,
+    zero: Instant,
+}
+
+impl Anchor {
+    /// Captures a new anchor relating the current cycle count to the current unix time.
+    pub fn new() -> Anchor {
+        Anchor {
+            unix_time_ns: unix_time_ns_now(),
+            zero: Instant::now(),
+        }
+    }
+}
+
+impl Default for Anchor {
+    fn default() -> Anchor {
+        Anchor::new()
+    }
+}
+
+fn unix_time_ns_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// An [`Instant`] that can be shared and updated across threads atomically.
+#[cfg(all(feature = "atomic", target_has_atomic = "64"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "atomic", target_has_atomic = "64"))))]
+#[derive(Debug)]
+pub struct Atomic(AtomicU64);
+
+#[cfg(all(feature = "atomic", target_has_atomic = "64"))]
+impl Atomic {
+    /// Creates a new `Atomic` initialized with `instant`.
+    pub fn new(instant: Instant) -> Atomic {
+        Atomic(AtomicU64::new(instant.as_inner()))
+    }
+
+    /// Loads the current value.
+    pub fn load(&self, order: Ordering) -> Instant {
+        Instant::from_inner(self.0.load(order))
+    }
+
+    /// Stores `instant`.
+    pub fn store(&self, instant: Instant, order: Ordering) {
+        self.0.store(instant.as_inner(), order)
+    }
+
+    /// Swaps in `instant`, returning the previous value.
+    pub fn swap(&self, instant: Instant, order: Ordering) -> Instant {
+        Instant::from_inner(self.0.swap(instant.as_inner(), order))
+    }
+}
+
+#[cfg(all(feature = "atomic", target_has_atomic = "64"))]
+impl From<Instant> for Atomic {
+    fn from(instant: Instant) -> Atomic {
+        Atomic::new(instant)
+    }
+}
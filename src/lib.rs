@@ -12,8 +12,9 @@
 //!
 //! ## Platform Support
 //!
-//! Currently, only the Linux on `x86` or `x86_64` is backed by Time Stamp Counter (TSC).
-//! On other platforms, `fastant` falls back to coarse time.
+//! Linux on `x86`/`x86_64` is backed by the Time Stamp Counter (TSC), and Linux on
+//! `aarch64` is backed by the architectural generic timer counter. On other platforms
+//! (including macOS on Apple Silicon, for now), `fastant` falls back to coarse time.
 //!
 //! ## Calibration
 //!
@@ -22,22 +23,70 @@
 //! factors with the assistance of a source wall clock. Once the deviation is beyond a crazy
 //! threshold, the calibration will fail, and then we will fall back to coarse time.
 //!
+//! The aarch64 generic timer counter is architecturally guaranteed to be monotonic, to
+//! tick at a constant rate, and to stay synchronized across cores, so it needs no such
+//! deviation calibration. Its firmware-reported frequency is still validated once
+//! against the wall clock, since it is occasionally misconfigured.
+//!
 //! This calibration is stored globally and reused. In order to start the calibration before any
 //! call to `fastant` as to make sure that the time spent on `fastant` is constant, we link the
 //! calibration into application's initialization linker section, so it'll get executed once the
 //! process starts.
 //!
+//! ## Miri
+//!
+//! Under [Miri](https://github.com/rust-lang/miri), `fastant` never touches the TSC or
+//! generic timer counter: both require inline asm that Miri cannot interpret. Instead
+//! `current_cycle()` and `is_tsc_available()` behave as if running on an unsupported
+//! platform, and transparently use the coarse time fallback.
+//!
+//! ## Mocking
+//!
+//! With the `mock` feature enabled, [`mock()`] installs a global mock clock so that
+//! tests can drive `Instant::now()` deterministically instead of relying on real sleeps.
+//!
+//! ## Upkeep
+//!
+//! With the `upkeep` feature enabled, [`Upkeep`] spawns a background thread that keeps
+//! a shared "recent time" refreshed, and [`Instant::recent`] reads it at essentially no
+//! cost. This trades accuracy for speed on hot paths that take many timestamps.
+//!
+//! ## Calendar time conversions
+//!
+//! The `system-time`, `chrono`, and `time` features each add a conversion from an
+//! [`Instant`] (plus an [`Anchor`]) to the corresponding crate's calendar time type, so
+//! downstream users don't have to re-derive real-world timestamps from
+//! [`Instant::as_unix_nanos`] themselves.
+//!
 //! **[See also the `Instant` type](Instant).**
 
+#[cfg(all(target_os = "linux", target_arch = "aarch64", not(miri)))]
+mod aarch64_now;
 mod instant;
-#[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64")))]
+#[cfg(feature = "mock")]
+mod mock;
+#[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64"), not(miri)))]
 mod tsc_now;
+#[cfg(feature = "upkeep")]
+mod upkeep;
 
 pub use instant::Anchor;
 #[cfg(all(feature = "atomic", target_has_atomic = "64"))]
 #[cfg_attr(docsrs, doc(cfg(all(feature = "atomic", target_has_atomic = "64"))))]
 pub use instant::Atomic;
 pub use instant::Instant;
+#[cfg(feature = "mock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub use mock::mock;
+#[cfg(feature = "mock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub use mock::Mock;
+#[cfg(feature = "mock")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+pub use mock::MockGuard;
+#[cfg(feature = "upkeep")]
+#[cfg_attr(docsrs, doc(cfg(feature = "upkeep")))]
+pub use upkeep::Upkeep;
 
 /// Return `true` if the current platform supports Time Stamp Counter (TSC),
 /// and the calibration has succeeded.
@@ -45,11 +94,20 @@ pub use instant::Instant;
 /// The result is always the same during the lifetime of the application process.
 #[inline]
 pub fn is_tsc_available() -> bool {
-    #[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64")))]
+    #[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64"), not(miri)))]
     {
         tsc_now::is_tsc_available()
     }
-    #[cfg(not(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64"))))]
+    // On Linux aarch64 the generic timer counter takes the TSC's place: this reports
+    // whether its firmware-reported frequency passed validation.
+    #[cfg(all(target_os = "linux", target_arch = "aarch64", not(miri)))]
+    {
+        aarch64_now::is_tsc_available()
+    }
+    #[cfg(not(any(
+        all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64"), not(miri)),
+        all(target_os = "linux", target_arch = "aarch64", not(miri)),
+    )))]
     {
         false
     }
@@ -57,7 +115,13 @@ pub fn is_tsc_available() -> bool {
 
 #[inline]
 pub(crate) fn current_cycle() -> u64 {
-    #[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64")))]
+    #[cfg(feature = "mock")]
+    {
+        if mock::is_installed() {
+            return mock::current_cycle();
+        }
+    }
+    #[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64"), not(miri)))]
     {
         if tsc_now::is_tsc_available() {
             tsc_now::current_cycle()
@@ -65,7 +129,18 @@ pub(crate) fn current_cycle() -> u64 {
             current_cycle_fallback()
         }
     }
-    #[cfg(not(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64"))))]
+    #[cfg(all(target_os = "linux", target_arch = "aarch64", not(miri)))]
+    {
+        if aarch64_now::is_tsc_available() {
+            aarch64_now::current_cycle()
+        } else {
+            current_cycle_fallback()
+        }
+    }
+    #[cfg(not(any(
+        all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64"), not(miri)),
+        all(target_os = "linux", target_arch = "aarch64", not(miri)),
+    )))]
     {
         current_cycle_fallback()
     }
@@ -87,11 +162,32 @@ pub(crate) fn current_cycle_fallback() -> u64 {
 
 #[inline]
 pub(crate) fn nanos_per_cycle() -> f64 {
-    #[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64")))]
+    #[cfg(feature = "mock")]
     {
-        tsc_now::nanos_per_cycle()
+        if mock::is_installed() {
+            return 1.0;
+        }
     }
-    #[cfg(not(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64"))))]
+    #[cfg(all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64"), not(miri)))]
+    {
+        if tsc_now::is_tsc_available() {
+            tsc_now::nanos_per_cycle()
+        } else {
+            1.0
+        }
+    }
+    #[cfg(all(target_os = "linux", target_arch = "aarch64", not(miri)))]
+    {
+        if aarch64_now::is_tsc_available() {
+            aarch64_now::nanos_per_cycle()
+        } else {
+            1.0
+        }
+    }
+    #[cfg(not(any(
+        all(target_os = "linux", any(target_arch = "x86", target_arch = "x86_64"), not(miri)),
+        all(target_os = "linux", target_arch = "aarch64", not(miri)),
+    )))]
     {
         1.0
     }
@@ -113,6 +209,12 @@ mod tests {
         let _ = is_tsc_available();
     }
 
+    #[test]
+    #[cfg(miri)]
+    fn test_miri_falls_back() {
+        assert!(!is_tsc_available());
+    }
+
     #[test]
     #[wasm_bindgen_test]
     fn test_monotonic() {
@@ -139,6 +241,56 @@ mod tests {
         assert!(unix_nanos > 0);
     }
 
+    #[test]
+    #[cfg(feature = "system-time")]
+    fn test_into_system_time() {
+        let now = Instant::now();
+        let anchor = Anchor::new();
+        let system_time = now.into_system_time(&anchor);
+        assert!(system_time >= std::time::UNIX_EPOCH);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_into_chrono_date_time() {
+        let now = Instant::now();
+        let anchor = Anchor::new();
+        let date_time = now.into_chrono_date_time(&anchor);
+        assert!(date_time.timestamp_nanos_opt().unwrap_or(0) > 0);
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn test_into_offset_date_time() {
+        let now = Instant::now();
+        let anchor = Anchor::new();
+        let date_time = now.into_offset_date_time(&anchor);
+        assert!(date_time.unix_timestamp_nanos() > 0);
+    }
+
+    #[test]
+    #[cfg(feature = "mock")]
+    fn test_mock() {
+        let (mock, _guard) = crate::mock();
+        let start = Instant::now();
+        mock.increment(Duration::from_secs(1));
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+        mock.set(start);
+        assert_eq!(start.elapsed(), Duration::from_secs(0));
+    }
+
+    #[test]
+    #[cfg(feature = "upkeep")]
+    fn test_recent() {
+        let upkeep = crate::Upkeep::new(Duration::from_millis(1));
+        std::thread::sleep(Duration::from_millis(20));
+        let recent = Instant::recent();
+        assert!(recent.elapsed() < Duration::from_millis(100));
+        drop(upkeep);
+        // Falls back to `now()` once the upkeep thread has stopped.
+        let _ = Instant::recent();
+    }
+
     #[test]
     fn test_duration() {
         let mut rng = rand::thread_rng();
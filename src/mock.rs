@@ -0,0 +1,91 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! A mockable clock source, for writing deterministic tests of time-dependent code.
+//!
+//! This mirrors the `mock` module of the [`quanta`](https://docs.rs/quanta) crate:
+//! installing a mock clock makes [`current_cycle`](crate::current_cycle) read from a
+//! global counter instead of the real hardware clock, and [`Mock`] lets the test drive
+//! that counter forward explicitly.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::Instant;
+
+static MOCK_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Installs a mock clock, returning a handle to control it and a guard that uninstalls
+/// it again once dropped.
+///
+/// While installed, [`Instant::now`](crate::Instant::now) (and everything built on top
+/// of it, such as [`Instant::elapsed`](crate::Instant::elapsed) and
+/// [`Instant::as_unix_nanos`](crate::Instant::as_unix_nanos)) reads the mock clock
+/// instead of the real one. Only one mock clock can be installed at a time; installing
+/// a second one while the first's guard is still alive will panic.
+///
+/// ```
+/// # #[cfg(feature = "mock")]
+/// # {
+/// use std::time::Duration;
+///
+/// let (mock, _guard) = fastant::mock();
+/// let start = fastant::Instant::now();
+/// mock.increment(Duration::from_secs(1));
+/// assert_eq!(start.elapsed(), Duration::from_secs(1));
+/// # }
+/// ```
+pub fn mock() -> (Mock, MockGuard) {
+    // Captured before flipping `MOCK_INSTALLED`: once installed, `Instant::now()` reads
+    // `MOCK_CYCLE` itself, so seeding it from `Instant::now()` afterwards would just read
+    // back the zero it was initialized with.
+    let seed = Instant::now().as_inner();
+    if MOCK_INSTALLED.swap(true, Ordering::SeqCst) {
+        panic!("a mock clock is already installed");
+    }
+    MOCK_CYCLE.store(seed, Ordering::SeqCst);
+    (Mock(Arc::new(())), MockGuard(()))
+}
+
+static MOCK_CYCLE: AtomicU64 = AtomicU64::new(0);
+
+#[inline]
+pub(crate) fn is_installed() -> bool {
+    MOCK_INSTALLED.load(Ordering::Relaxed)
+}
+
+#[inline]
+pub(crate) fn current_cycle() -> u64 {
+    MOCK_CYCLE.load(Ordering::SeqCst)
+}
+
+/// A handle to the installed mock clock, used to advance or set the time it reports.
+///
+/// Obtained from [`mock()`]. `Mock` is cheaply cloneable; every clone controls the same
+/// underlying counter.
+#[derive(Clone, Debug)]
+pub struct Mock(Arc<()>);
+
+impl Mock {
+    /// Advances the mock clock forward by `duration`.
+    pub fn increment(&self, duration: Duration) {
+        MOCK_CYCLE.fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+    }
+
+    /// Sets the mock clock to read exactly `instant`.
+    pub fn set(&self, instant: Instant) {
+        MOCK_CYCLE.store(instant.as_inner(), Ordering::SeqCst);
+    }
+}
+
+/// Uninstalls the mock clock when dropped, restoring the real clock source.
+#[derive(Debug)]
+pub struct MockGuard(());
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        MOCK_INSTALLED.store(false, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Time Stamp Counter (TSC) backend for Linux on `x86`/`x86_64`.
+//!
+//! The calibration runs once, at most, and is triggered from a linker init section so
+//! that it has already completed by the time any application code has a chance to call
+//! into `fastant`.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant as StdInstant;
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::_rdtsc;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::_rdtsc;
+
+/// Number of wall-clock samples taken while calibrating the TSC frequency.
+const CALIBRATE_ROUNDS: u32 = 5;
+/// Wall-clock duration of each calibration round.
+const CALIBRATE_INTERVAL: Duration = Duration::from_millis(10);
+/// If any round's measured nanos-per-cycle deviates from the median of all rounds by more
+/// than this fraction, TSC is untrusted.
+const MAX_DEVIATION: f64 = 0.1;
+
+static TSC_AVAILABLE: AtomicBool = AtomicBool::new(false);
+static NANOS_PER_CYCLE_BITS: AtomicU64 = AtomicU64::new(0);
+
+#[used]
+#[cfg_attr(target_os = "linux", link_section = ".init_array")]
+static CALIBRATE_ON_LOAD: extern "C" fn() = calibrate_ctor;
+
+extern "C" fn calibrate_ctor() {
+    calibrate();
+}
+
+#[inline]
+pub fn current_cycle() -> u64 {
+    unsafe { _rdtsc() }
+}
+
+#[inline]
+pub fn is_tsc_available() -> bool {
+    TSC_AVAILABLE.load(Ordering::Relaxed)
+}
+
+#[inline]
+pub fn nanos_per_cycle() -> f64 {
+    f64::from_bits(NANOS_PER_CYCLE_BITS.load(Ordering::Relaxed))
+}
+
+/// Measures the TSC frequency against the wall clock and records the result. Safe to
+/// call more than once; later calls simply overwrite the calibration.
+fn calibrate() {
+    let mut measured = Vec::with_capacity(CALIBRATE_ROUNDS as usize);
+    for _ in 0..CALIBRATE_ROUNDS {
+        let wall_start = StdInstant::now();
+        let cycle_start = current_cycle();
+        std::thread::sleep(CALIBRATE_INTERVAL);
+        let cycle_end = current_cycle();
+        let wall_elapsed = wall_start.elapsed();
+
+        let cycles = cycle_end.saturating_sub(cycle_start);
+        if cycles == 0 {
+            continue;
+        }
+        measured.push(wall_elapsed.as_nanos() as f64 / cycles as f64);
+    }
+
+    if measured.is_empty() {
+        TSC_AVAILABLE.store(false, Ordering::Relaxed);
+        return;
+    }
+
+    measured.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let median = measured[measured.len() / 2];
+    let deviates = measured
+        .iter()
+        .any(|sample| ((sample - median) / median).abs() > MAX_DEVIATION);
+
+    NANOS_PER_CYCLE_BITS.store(median.to_bits(), Ordering::Relaxed);
+    TSC_AVAILABLE.store(!deviates, Ordering::Relaxed);
+}
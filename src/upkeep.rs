@@ -0,0 +1,87 @@
+// Copyright 2021 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! A background "recent time" upkeep thread, for essentially free reads of an
+//! approximate clock.
+//!
+//! This mirrors the `upkeep` module of the [`quanta`](https://docs.rs/quanta) crate:
+//! rather than paying the cost of a real clock read on every call, a single background
+//! thread periodically refreshes a shared atomic, and [`Instant::recent`](crate::Instant::recent)
+//! just loads it.
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::current_cycle;
+
+static RECENT_CYCLE: AtomicU64 = AtomicU64::new(0);
+static RECENT_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+#[inline]
+pub(crate) fn is_active() -> bool {
+    RECENT_ACTIVE.load(Ordering::Relaxed)
+}
+
+#[inline]
+pub(crate) fn recent_cycle() -> u64 {
+    RECENT_CYCLE.load(Ordering::Relaxed)
+}
+
+/// A handle to a background thread that refreshes the "recent time" used by
+/// [`Instant::recent`](crate::Instant::recent).
+///
+/// The thread runs for as long as the `Upkeep` handle is alive, and is stopped and
+/// joined when the handle is dropped.
+#[derive(Debug)]
+pub struct Upkeep {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Upkeep {
+    /// Spawns the upkeep thread, refreshing the recent time every `interval`.
+    ///
+    /// ```
+    /// # #[cfg(feature = "upkeep")]
+    /// # {
+    /// use std::time::Duration;
+    ///
+    /// let _upkeep = fastant::Upkeep::new(Duration::from_millis(10));
+    /// let _recent = fastant::Instant::recent();
+    /// # }
+    /// ```
+    pub fn new(interval: Duration) -> Upkeep {
+        RECENT_CYCLE.store(current_cycle(), Ordering::Relaxed);
+        RECENT_ACTIVE.store(true, Ordering::SeqCst);
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let handle = std::thread::Builder::new()
+            .name("fastant-upkeep".to_owned())
+            .spawn(move || {
+                while !thread_stop.load(Ordering::Relaxed) {
+                    RECENT_CYCLE.store(current_cycle(), Ordering::Relaxed);
+                    std::thread::sleep(interval);
+                }
+            })
+            .expect("failed to spawn fastant upkeep thread");
+
+        Upkeep {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Upkeep {
+    fn drop(&mut self) {
+        RECENT_ACTIVE.store(false, Ordering::SeqCst);
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}